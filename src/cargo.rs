@@ -0,0 +1,52 @@
+//! Shelling out to `cargo` (and `git push`) for the parts of a release that
+//! aren't covered by `git2`: refreshing the lockfile, packaging the crate,
+//! and publishing it to crates.io.
+
+use std::env;
+use std::process::Command;
+
+use Config;
+
+pub fn update_lockfile(config: &Config) -> bool {
+    run_cargo(config, &["fetch".to_owned()])
+}
+
+pub fn package(config: &Config) -> bool {
+    run_cargo(config, &["package".to_owned()])
+}
+
+/// Pushes the release commit and tag to `origin`, then runs `cargo publish`,
+/// honoring a `CARGO_REGISTRY_TOKEN` environment variable if one is set.
+/// Callers are expected to only invoke this in write mode, behind whatever
+/// config/CLI option opts the user into publishing.
+pub fn publish(config: &Config) -> bool {
+    if !push(config) {
+        return false;
+    }
+
+    let mut args = vec!["publish".to_owned()];
+    if let Ok(token) = env::var("CARGO_REGISTRY_TOKEN") {
+        args.push("--token".to_owned());
+        args.push(token);
+    }
+
+    run_cargo(config, &args)
+}
+
+fn push(config: &Config) -> bool {
+    Command::new("git")
+        .args(&["push", "--follow-tags", "origin", "HEAD"])
+        .current_dir(&config.repository_path)
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+fn run_cargo(config: &Config, args: &[String]) -> bool {
+    Command::new("cargo")
+        .args(args)
+        .current_dir(&config.repository_path)
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
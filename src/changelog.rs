@@ -0,0 +1,259 @@
+//! Template-driven changelog rendering.
+//!
+//! The actual formatting lives in a [Tera](https://tera.netlify.app)
+//! template, `changelog.tera`, optionally checked into the root of the
+//! repository. When that file is absent `DEFAULT_TEMPLATE` is used instead,
+//! so `generate`/`write` always produce something sensible out of the box.
+//!
+//! The `CHANGELOG.md` entry and the annotated tag message are rendered from
+//! the same template. The tag message covers the commits since the last
+//! tag; so does each `CHANGELOG.md` entry, except the very first one, which
+//! covers the whole history (see `write`).
+
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::Path;
+
+use tera::{Context, Tera};
+use serde_json::{Map, Value};
+use time;
+
+use commit_analyzer::{self, Commit, CommitType};
+use git;
+use Config;
+
+const TEMPLATE_FILE_NAME: &'static str = "changelog.tera";
+
+const DEFAULT_TEMPLATE: &'static str = "\
+## {{ version }} ({{ date }})
+{% for section in sections %}
+### {{ section.title }}
+{% for scope in section.scopes %}
+{% if scope.scope %}
+#### {{ scope.scope }}
+{% endif %}
+{% for commit in scope.commits %}
+- {{ commit.subject }}{% if commit.breaking %} (BREAKING CHANGE){% endif %}
+{% endfor %}
+{% endfor %}
+{% endfor %}
+";
+
+#[derive(Debug)]
+pub enum ChangelogError {
+    Git(::git2::Error),
+    Render(::tera::Error),
+    Io(::std::io::Error),
+}
+
+impl From<::git2::Error> for ChangelogError {
+    fn from(err: ::git2::Error) -> ChangelogError {
+        ChangelogError::Git(err)
+    }
+}
+
+impl From<::tera::Error> for ChangelogError {
+    fn from(err: ::tera::Error) -> ChangelogError {
+        ChangelogError::Render(err)
+    }
+}
+
+impl From<::std::io::Error> for ChangelogError {
+    fn from(err: ::std::io::Error) -> ChangelogError {
+        ChangelogError::Io(err)
+    }
+}
+
+/// Renders the changelog for the commits since the last tag, using either
+/// the project's `changelog.tera` or the built-in default template.
+pub fn generate(config: &Config) -> Result<String, ChangelogError> {
+    let commits = try!(git::commits_since_last_tag(config));
+
+    let parsed: Vec<Commit> = commits.iter()
+        .map(|commit| commit_analyzer::parse_message(commit.message().unwrap_or(""), &config.commit_types))
+        .filter(|commit| match config.scope_filter {
+            Some(ref scope) => commit.scope.as_ref() == Some(scope),
+            None => true,
+        })
+        .collect();
+
+    render(config, &parsed)
+}
+
+/// Renders every release in the repository's history in one pass, using
+/// [`git::retrieve_commit_tag_map`](../git/fn.retrieve_commit_tag_map.html)
+/// to attribute each commit to the tag it originally shipped in instead of
+/// only covering the unreleased range.
+pub fn generate_history(config: &Config) -> Result<String, ChangelogError> {
+    let tag_map = try!(git::retrieve_commit_tag_map(&config.repository));
+
+    let mut revwalk = try!(config.repository.revwalk());
+    try!(revwalk.push_head());
+
+    let mut releases: Vec<(String, Vec<Commit>)> = vec![(config.new_version_string(), Vec::new())];
+
+    for oid in revwalk {
+        let oid = try!(oid);
+        let commit = try!(config.repository.find_commit(oid));
+
+        // The commit a tag points at belongs to that tag's own release, not
+        // the newer one being accumulated so far, so open its bucket before
+        // appending.
+        if let Some(tag_name) = tag_map.get(&oid) {
+            releases.push((tag_name.clone(), Vec::new()));
+        }
+
+        let parsed = commit_analyzer::parse_message(commit.message().unwrap_or(""), &config.commit_types);
+        let in_scope = match config.scope_filter {
+            Some(ref scope) => parsed.scope.as_ref() == Some(scope),
+            None => true,
+        };
+        if in_scope {
+            releases.last_mut().unwrap().1.push(parsed);
+        }
+    }
+
+    let mut rendered = Vec::new();
+    for (version, mut commits) in releases {
+        commits.reverse();
+        if !commits.is_empty() {
+            rendered.push(try!(render_version(config, &version, &commits)));
+        }
+    }
+
+    Ok(rendered.join("\n"))
+}
+
+/// Renders the changelog and prepends it to `config.changelog_filename`.
+///
+/// The very first time a project writes a changelog there is nothing to
+/// prepend to, so instead of rendering just the unreleased range this
+/// bootstraps the file with `generate_history`, attributing every past
+/// commit to the tag it originally shipped in.
+pub fn write(config: &Config) -> Result<(), ChangelogError> {
+    let changelog_path = config.repository_path.join(&config.changelog_filename);
+    let changelog_exists = changelog_path.exists();
+
+    let rendered = if changelog_exists {
+        try!(generate(config))
+    }
+    else {
+        try!(generate_history(config))
+    };
+
+    let mut existing = String::new();
+    if changelog_exists {
+        try!(File::open(&changelog_path).and_then(|mut f| f.read_to_string(&mut existing)));
+    }
+
+    let mut file = try!(File::create(&changelog_path));
+    try!(file.write_all(rendered.as_bytes()));
+    if !existing.is_empty() {
+        try!(file.write_all(b"\n"));
+        try!(file.write_all(existing.as_bytes()));
+    }
+
+    Ok(())
+}
+
+fn render(config: &Config, commits: &[Commit]) -> Result<String, ChangelogError> {
+    render_version(config, &config.new_version_string(), commits)
+}
+
+fn render_version(config: &Config, version: &str, commits: &[Commit]) -> Result<String, ChangelogError> {
+    let mut context = Context::new();
+    context.add("version", &version);
+    context.add("date", &today());
+    context.add("repository_url", &repository_url(config));
+    context.add("sections", &group_commits(commits));
+
+    let template = try!(load_template(config));
+
+    Ok(try!(Tera::one_off(&template, &context, false)))
+}
+
+fn load_template(config: &Config) -> Result<String, ChangelogError> {
+    let template_path = config.repository_path.join(TEMPLATE_FILE_NAME);
+
+    if !template_path.exists() {
+        return Ok(DEFAULT_TEMPLATE.to_owned());
+    }
+
+    let mut template = String::new();
+    try!(File::open(&template_path).and_then(|mut f| f.read_to_string(&mut template)));
+    Ok(template)
+}
+
+fn today() -> String {
+    time::strftime("%Y-%m-%d", &time::now()).unwrap_or_default()
+}
+
+fn repository_url(config: &Config) -> String {
+    config.repository.find_remote("origin").ok()
+        .and_then(|remote| remote.url().map(String::from))
+        .unwrap_or_default()
+}
+
+/// Buckets commits into the `Breaking Changes` / `Features` / `Bug Fixes` /
+/// `Other` sections, then further groups each section by scope.
+fn group_commits(commits: &[Commit]) -> Value {
+    let mut sections: Vec<(&'static str, Vec<&Commit>)> = Vec::new();
+
+    for commit in commits {
+        if commit.commit_type == CommitType::Unknown {
+            continue;
+        }
+
+        let title = section_title(commit);
+        match sections.iter_mut().find(|section| section.0 == title) {
+            Some(section) => section.1.push(commit),
+            None => sections.push((title, vec![commit])),
+        }
+    }
+
+    Value::Array(sections.into_iter().map(|(title, commits)| {
+        let mut map = Map::new();
+        map.insert("title".to_owned(), Value::String(title.to_owned()));
+        map.insert("scopes".to_owned(), group_by_scope(&commits));
+        Value::Object(map)
+    }).collect())
+}
+
+fn section_title(commit: &Commit) -> &'static str {
+    if commit.breaking {
+        "Breaking Changes"
+    }
+    else {
+        match commit.commit_type {
+            CommitType::Minor => "Features",
+            CommitType::Patch => "Bug Fixes",
+            _ => "Other",
+        }
+    }
+}
+
+fn group_by_scope(commits: &[&Commit]) -> Value {
+    let mut scopes: Vec<(Option<String>, Vec<&Commit>)> = Vec::new();
+
+    for commit in commits {
+        match scopes.iter_mut().find(|scope| &scope.0 == &commit.scope) {
+            Some(scope) => scope.1.push(commit),
+            None => scopes.push((commit.scope.clone(), vec![commit])),
+        }
+    }
+
+    Value::Array(scopes.into_iter().map(|(scope, commits)| {
+        let mut map = Map::new();
+        map.insert("scope".to_owned(), scope.map(Value::String).unwrap_or(Value::Null));
+        map.insert("commits".to_owned(), Value::Array(commits.into_iter().map(commit_to_value).collect()));
+        Value::Object(map)
+    }).collect())
+}
+
+fn commit_to_value(commit: &Commit) -> Value {
+    let mut map = Map::new();
+    map.insert("scope".to_owned(), commit.scope.clone().map(Value::String).unwrap_or(Value::Null));
+    map.insert("subject".to_owned(), Value::String(commit.subject.clone()));
+    map.insert("breaking".to_owned(), Value::Bool(commit.breaking));
+    Value::Object(map)
+}
@@ -0,0 +1,322 @@
+//! Parsing of commit messages according to the
+//! [Conventional Commits](https://www.conventionalcommits.org) grammar.
+//!
+//! A conforming header looks like `type(scope)!: description`, optionally
+//! followed by a body and a list of footer tokens (`Token: value` or
+//! `Token #value`). `commit_types` maps each type keyword to the version
+//! bump it triggers; callers can extend or override the defaults (`feat`,
+//! `fix`), e.g. when a `.semantic-rs.toml` defines additional keywords.
+
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommitType {
+    Unknown,
+    Patch,
+    Minor,
+    Major,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commit {
+    pub commit_type: CommitType,
+    pub type_name: String,
+    pub scope: Option<String>,
+    pub breaking: bool,
+    pub subject: String,
+    pub body: Option<String>,
+}
+
+const BREAKING_CHANGE_FOOTER_TOKENS: [&'static str; 2] = ["BREAKING CHANGE", "BREAKING-CHANGE"];
+
+/// The conventional-commits types semantic-rs understands out of the box.
+/// A `.semantic-rs.toml` `commit_types` table is merged on top of this, so
+/// projects can add or override keywords without losing the defaults.
+pub fn default_commit_types() -> HashMap<String, String> {
+    let mut defaults = HashMap::new();
+    defaults.insert("feat".to_owned(), "minor".to_owned());
+    defaults.insert("fix".to_owned(), "patch".to_owned());
+    defaults
+}
+
+/// Parses a single commit message and classifies it.
+///
+/// `commit_types` maps a conventional-commits `type` keyword to the bump
+/// level it should trigger (`"patch"`, `"minor"` or `"major"`); any type
+/// absent from the map, or a message that doesn't parse as a conventional
+/// commit at all (e.g. a merge commit), is treated as `CommitType::Unknown`
+/// and doesn't trigger a version bump. A conforming commit marked as
+/// breaking (`!` in the header or a breaking-change footer) is always
+/// `CommitType::Major`, whatever its type.
+pub fn parse_message(message: &str, commit_types: &HashMap<String, String>) -> Commit {
+    let mut lines = message.lines();
+
+    let header = match lines.next() {
+        Some(header) => header,
+        None => return unknown_commit(message),
+    };
+
+    let (type_and_scope, subject) = match header.find(':') {
+        Some(idx) => (header[..idx].trim(), header[idx + 1..].trim()),
+        None => return unknown_commit(message),
+    };
+
+    let header_breaking = type_and_scope.ends_with('!');
+    let type_and_scope = type_and_scope.trim_end_matches('!');
+
+    let (type_name, scope) = match (type_and_scope.find('('), type_and_scope.ends_with(')')) {
+        (Some(open), true) => {
+            let type_name = type_and_scope[..open].trim();
+            let scope = type_and_scope[open + 1..type_and_scope.len() - 1].trim();
+            (type_name, Some(scope.to_owned()))
+        }
+        _ => (type_and_scope.trim(), None),
+    };
+
+    if type_name.is_empty() || subject.is_empty() {
+        return unknown_commit(message);
+    }
+
+    let rest: Vec<&str> = lines.collect();
+    let (body, footer_breaking) = split_body_and_footers(&rest);
+
+    let commit_type = commit_types.get(type_name)
+        .map(|bump| bump_from_str(bump))
+        .unwrap_or(CommitType::Unknown);
+
+    let breaking = header_breaking || footer_breaking;
+
+    Commit {
+        commit_type: if breaking { CommitType::Major } else { commit_type },
+        type_name: type_name.to_owned(),
+        scope: scope,
+        breaking: breaking,
+        subject: subject.to_owned(),
+        body: body,
+    }
+}
+
+fn unknown_commit(message: &str) -> Commit {
+    let subject = message.lines().next().unwrap_or("").to_owned();
+
+    Commit {
+        commit_type: CommitType::Unknown,
+        type_name: "unknown".to_owned(),
+        scope: None,
+        breaking: false,
+        subject: subject,
+        body: None,
+    }
+}
+
+fn bump_from_str(bump: &str) -> CommitType {
+    match bump {
+        "major" => CommitType::Major,
+        "minor" => CommitType::Minor,
+        "patch" => CommitType::Patch,
+        _ => CommitType::Unknown,
+    }
+}
+
+/// Splits the lines following the header into the free-form body and any
+/// trailing `Token: value` / `Token #value` footers, reporting whether one
+/// of the footers is a `BREAKING CHANGE`/`BREAKING-CHANGE` marker.
+///
+/// Conventional Commits footers are required to follow a blank line, so a
+/// lone paragraph that merely *looks* like a trailer (`Fixes: the thing`)
+/// is only recognized as a footer once it trails an actual body paragraph;
+/// the well-known `BREAKING CHANGE`/`BREAKING-CHANGE` marker is always
+/// recognized, even without a preceding body, since it has no other way to
+/// be expressed.
+fn split_body_and_footers(lines: &[&str]) -> (Option<String>, bool) {
+    let paragraphs = split_into_paragraphs(lines);
+    let has_body_paragraph = paragraphs.len() >= 2;
+
+    let is_footer_paragraph = paragraphs.last().map_or(false, |footer| {
+        let has_breaking_marker = footer.iter().any(|line| is_breaking_change_footer(line));
+        !footer.is_empty() && footer.iter().all(|line| {
+            is_breaking_change_footer(line) || ((has_body_paragraph || has_breaking_marker) && is_footer_token(line))
+        })
+    });
+
+    if !is_footer_paragraph {
+        return (join_non_empty(lines), false);
+    }
+
+    let footer_lines = paragraphs.last().unwrap();
+    let breaking = footer_lines.iter().any(|line| is_breaking_change_footer(line));
+
+    let body_lines: Vec<&str> = paragraphs[..paragraphs.len() - 1].iter()
+        .flat_map(|paragraph| paragraph.iter().cloned())
+        .collect();
+
+    (join_non_empty(&body_lines), breaking)
+}
+
+/// Splits `lines` into paragraphs separated by blank lines, dropping the
+/// blank lines themselves.
+fn split_into_paragraphs<'a>(lines: &[&'a str]) -> Vec<Vec<&'a str>> {
+    let mut paragraphs = Vec::new();
+    let mut current = Vec::new();
+
+    for line in lines {
+        if line.trim().is_empty() {
+            if !current.is_empty() {
+                paragraphs.push(current);
+                current = Vec::new();
+            }
+        }
+        else {
+            current.push(*line);
+        }
+    }
+
+    if !current.is_empty() {
+        paragraphs.push(current);
+    }
+
+    paragraphs
+}
+
+fn is_breaking_change_footer(line: &str) -> bool {
+    let bytes = line.as_bytes();
+
+    BREAKING_CHANGE_FOOTER_TOKENS.iter().any(|token| {
+        // Compare as bytes rather than slicing `line` by `str` index: a
+        // match can only succeed if every compared byte is plain ASCII, so
+        // `token.len()` is guaranteed to land on a char boundary once we
+        // reach the `line[token.len()..]` slice below.
+        if bytes.len() <= token.len() || !bytes[..token.len()].eq_ignore_ascii_case(token.as_bytes()) {
+            return false;
+        }
+
+        match line[token.len()..].trim_start().chars().next() {
+            Some(':') | Some('#') => true,
+            _ => false,
+        }
+    })
+}
+
+fn join_non_empty(lines: &[&str]) -> Option<String> {
+    let body = lines.iter().cloned()
+        .skip_while(|line| line.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let body = body.trim();
+
+    if body.is_empty() { None } else { Some(body.to_owned()) }
+}
+
+fn is_footer_token(line: &str) -> bool {
+    let line = line.trim_start();
+    let token_end = line.find(':').or_else(|| line.find(" #"));
+
+    match token_end {
+        Some(idx) => !line[..idx].trim().is_empty() && line[..idx].chars().all(|c| c.is_alphanumeric() || c == '-'),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit_types() -> HashMap<String, String> {
+        let mut types = default_commit_types();
+        types.insert("chore".to_owned(), "patch".to_owned());
+        types
+    }
+
+    #[test]
+    fn feat_bumps_minor() {
+        let commit = parse_message("feat: add the thing", &commit_types());
+
+        assert_eq!(commit.commit_type, CommitType::Minor);
+        assert_eq!(commit.type_name, "feat");
+        assert_eq!(commit.scope, None);
+        assert!(!commit.breaking);
+    }
+
+    #[test]
+    fn fix_bumps_patch() {
+        let commit = parse_message("fix: squash the bug", &commit_types());
+
+        assert_eq!(commit.commit_type, CommitType::Patch);
+    }
+
+    #[test]
+    fn scope_and_bang_together_are_breaking() {
+        let commit = parse_message("feat(api)!: remove the old endpoint", &commit_types());
+
+        assert_eq!(commit.commit_type, CommitType::Major);
+        assert_eq!(commit.type_name, "feat");
+        assert_eq!(commit.scope, Some("api".to_owned()));
+        assert!(commit.breaking);
+    }
+
+    #[test]
+    fn breaking_change_footer_forces_major() {
+        let message = "fix: patch the thing\n\nSome context for the change.\n\nBREAKING CHANGE: callers must update their config";
+        let commit = parse_message(message, &commit_types());
+
+        assert_eq!(commit.commit_type, CommitType::Major);
+        assert!(commit.breaking);
+        assert_eq!(commit.body, Some("Some context for the change.".to_owned()));
+    }
+
+    #[test]
+    fn breaking_change_footer_is_case_insensitive() {
+        let message = "fix: patch the thing\n\nbreaking change: callers must update their config";
+        let commit = parse_message(message, &commit_types());
+
+        assert!(commit.breaking);
+    }
+
+    #[test]
+    fn breaking_dash_change_footer_is_recognized() {
+        let message = "fix: patch the thing\n\nBREAKING-CHANGE: callers must update their config";
+        let commit = parse_message(message, &commit_types());
+
+        assert!(commit.breaking);
+    }
+
+    #[test]
+    fn other_footer_spellings_do_not_force_major() {
+        let message = "fix: patch the thing\n\nNote that this is not a breaking change footer.";
+        let commit = parse_message(message, &commit_types());
+
+        assert_eq!(commit.commit_type, CommitType::Patch);
+        assert!(!commit.breaking);
+    }
+
+    #[test]
+    fn merge_commit_is_unknown() {
+        let commit = parse_message("Merge branch 'main' into feature/foo", &commit_types());
+
+        assert_eq!(commit.commit_type, CommitType::Unknown);
+    }
+
+    #[test]
+    fn non_conforming_message_is_unknown() {
+        let commit = parse_message("fixed the thing without a conventional header", &commit_types());
+
+        assert_eq!(commit.commit_type, CommitType::Unknown);
+    }
+
+    #[test]
+    fn unmapped_type_is_unknown() {
+        let commit = parse_message("docs: update the README", &commit_types());
+
+        assert_eq!(commit.commit_type, CommitType::Unknown);
+        assert_eq!(commit.type_name, "docs");
+    }
+
+    #[test]
+    fn sole_trailer_like_paragraph_is_treated_as_body() {
+        let message = "fix: patch the thing\n\nFixes: the other thing";
+        let commit = parse_message(message, &commit_types());
+
+        assert_eq!(commit.body, Some("Fixes: the other thing".to_owned()));
+        assert!(!commit.breaking);
+    }
+}
@@ -0,0 +1,93 @@
+//! Project-local configuration loaded from a `.semantic-rs.toml` file in the
+//! root of the repository.
+//!
+//! Every field is optional: a missing file, or a missing key inside an
+//! existing file, simply means "use the built-in default". Values coming
+//! from the command line always win over whatever is found here.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use toml::{Parser, Table, Value};
+
+const CONFIG_FILE_NAME: &'static str = ".semantic-rs.toml";
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Parse,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConfigError::Io(ref e) => write!(f, "could not read `{}`: {}", CONFIG_FILE_NAME, e),
+            ConfigError::Parse => write!(f, "`{}` is not valid TOML", CONFIG_FILE_NAME),
+        }
+    }
+}
+
+impl From<io::Error> for ConfigError {
+    fn from(err: io::Error) -> ConfigError {
+        ConfigError::Io(err)
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct FileConfig {
+    pub changelog_filename: Option<String>,
+    pub tag_name_template: Option<String>,
+    pub commit_message_template: Option<String>,
+    pub commit_types: Option<HashMap<String, String>>,
+    pub branch_whitelist: Option<Vec<String>>,
+    pub publish: Option<bool>,
+}
+
+impl FileConfig {
+    /// Looks for a `.semantic-rs.toml` directly inside `repository_path` and
+    /// parses it. Returns an all-`None` `FileConfig` when the file does not
+    /// exist, so callers can unconditionally fall back to their own
+    /// defaults with `unwrap_or_else`.
+    pub fn from_repository_path<P: AsRef<Path>>(repository_path: P) -> Result<FileConfig, ConfigError> {
+        let config_path = repository_path.as_ref().join(CONFIG_FILE_NAME);
+
+        if !config_path.exists() {
+            return Ok(FileConfig::default());
+        }
+
+        let mut contents = String::new();
+        try!(File::open(&config_path).and_then(|mut f| f.read_to_string(&mut contents)));
+
+        let table = try!(Parser::new(&contents).parse().ok_or(ConfigError::Parse));
+
+        Ok(FileConfig {
+            changelog_filename: lookup_string(&table, "changelog"),
+            tag_name_template: lookup_string(&table, "tag_name_template"),
+            commit_message_template: lookup_string(&table, "commit_message_template"),
+            commit_types: lookup_commit_types(&table),
+            branch_whitelist: lookup_string_array(&table, "branch"),
+            publish: table.get("publish").and_then(Value::as_bool),
+        })
+    }
+}
+
+fn lookup_string(table: &Table, key: &str) -> Option<String> {
+    table.get(key).and_then(Value::as_str).map(String::from)
+}
+
+fn lookup_string_array(table: &Table, key: &str) -> Option<Vec<String>> {
+    table.get(key).and_then(Value::as_slice).map(|values| {
+        values.iter().filter_map(Value::as_str).map(String::from).collect()
+    })
+}
+
+fn lookup_commit_types(table: &Table) -> Option<HashMap<String, String>> {
+    table.get("commit_types").and_then(Value::as_table).map(|types| {
+        types.iter()
+            .filter_map(|(keyword, bump)| bump.as_str().map(|bump| (keyword.clone(), bump.to_string())))
+            .collect()
+    })
+}
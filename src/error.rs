@@ -0,0 +1,79 @@
+//! A crate-wide error type with one variant per release stage, so `main`
+//! can report a distinct, scriptable exit code for whichever step failed
+//! instead of always exiting with `1`.
+
+use std::fmt;
+use std::io;
+
+use git2;
+
+use changelog::ChangelogError;
+use config::ConfigError;
+
+#[derive(Debug)]
+pub enum Error {
+    OpenRepository(git2::Error),
+    Signature(git2::Error),
+    Config(ConfigError),
+    ReadManifest(io::Error),
+    WriteManifest(io::Error),
+    CommitAnalysis(git2::Error),
+    Changelog(ChangelogError),
+    Lockfile,
+    Package,
+    Commit(git2::Error),
+    Tag(git2::Error),
+    Publish,
+}
+
+impl From<ConfigError> for Error {
+    fn from(err: ConfigError) -> Error {
+        Error::Config(err)
+    }
+}
+
+impl From<ChangelogError> for Error {
+    fn from(err: ChangelogError) -> Error {
+        Error::Changelog(err)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::OpenRepository(ref e) => write!(f, "Could not open the git repository: {}", e),
+            Error::Signature(ref e) => write!(f, "Failed to get the committer's name and email address: {}", e),
+            Error::Config(ref e) => write!(f, "Reading `.semantic-rs.toml` failed: {}", e),
+            Error::ReadManifest(ref e) => write!(f, "Reading `Cargo.toml` failed: {}", e),
+            Error::WriteManifest(ref e) => write!(f, "Writing `Cargo.toml` failed: {}", e),
+            Error::CommitAnalysis(ref e) => write!(f, "Analyzing commits failed: {}", e),
+            Error::Changelog(ref e) => write!(f, "Generating the changelog failed: {:?}", e),
+            Error::Lockfile => write!(f, "`cargo fetch` failed. See above for the cargo error message."),
+            Error::Package => write!(f, "`cargo package` failed. See above for the cargo error message."),
+            Error::Commit(ref e) => write!(f, "Committing the release files failed: {}", e),
+            Error::Tag(ref e) => write!(f, "Failed to create the git tag: {}", e),
+            Error::Publish => write!(f, "`cargo publish` failed. See above for the cargo error message."),
+        }
+    }
+}
+
+impl Error {
+    /// A distinct process exit code per failure stage, so a CI pipeline can
+    /// branch on why `semantic-rs` failed instead of just that it did.
+    pub fn exit_code(&self) -> i32 {
+        match *self {
+            Error::OpenRepository(_) => 1,
+            Error::Signature(_) => 2,
+            Error::Config(_) => 3,
+            Error::ReadManifest(_) => 4,
+            Error::WriteManifest(_) => 5,
+            Error::CommitAnalysis(_) => 6,
+            Error::Changelog(_) => 7,
+            Error::Lockfile => 8,
+            Error::Package => 9,
+            Error::Commit(_) => 10,
+            Error::Tag(_) => 11,
+            Error::Publish => 12,
+        }
+    }
+}
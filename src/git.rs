@@ -0,0 +1,134 @@
+//! Thin wrappers around the `git2` operations semantic-rs needs: finding a
+//! committer signature, walking commits since the last release, and
+//! creating the release commit and tag.
+
+use std::collections::HashMap;
+use std::env;
+use std::path::Path;
+
+use git2;
+use git2::{Commit, Oid, Repository, Signature};
+
+use commit_analyzer::{self, CommitType};
+use Config;
+
+/// Looks up a committer signature, preferring the `GIT_COMMITTER_NAME` /
+/// `GIT_COMMITTER_EMAIL` environment variables over the repository's local,
+/// user or global git config.
+pub fn get_signature(repository: &Repository) -> Result<Signature<'static>, git2::Error> {
+    if let (Ok(name), Ok(email)) = (env::var("GIT_COMMITTER_NAME"), env::var("GIT_COMMITTER_EMAIL")) {
+        return Signature::now(&name, &email);
+    }
+
+    repository.signature()
+}
+
+/// Determines the highest version bump required by any commit since the
+/// last tag.
+pub fn version_bump_since_latest(config: &Config) -> Result<CommitType, git2::Error> {
+    let commits = try!(commits_since_last_tag(config));
+
+    Ok(commits.iter()
+        .map(|commit| commit_analyzer::parse_message(commit.message().unwrap_or(""), &config.commit_types).commit_type)
+        .max_by_key(|bump| bump_rank(*bump))
+        .unwrap_or(CommitType::Unknown))
+}
+
+fn bump_rank(bump: CommitType) -> u8 {
+    match bump {
+        CommitType::Unknown => 0,
+        CommitType::Patch => 1,
+        CommitType::Minor => 2,
+        CommitType::Major => 3,
+    }
+}
+
+/// Returns every commit reachable from `HEAD` down to, but excluding, the
+/// most recently created tag, oldest first.
+pub fn commits_since_last_tag(config: &Config) -> Result<Vec<Commit>, git2::Error> {
+    let repo = &config.repository;
+    let last_tag_commit = latest_tag_target(repo);
+
+    let mut revwalk = try!(repo.revwalk());
+    try!(revwalk.push_head());
+
+    let mut commits = Vec::new();
+    for oid in revwalk {
+        let oid = try!(oid);
+
+        if Some(oid) == last_tag_commit {
+            break;
+        }
+
+        commits.push(try!(repo.find_commit(oid)));
+    }
+
+    commits.reverse();
+    Ok(commits)
+}
+
+fn latest_tag_target(repo: &Repository) -> Option<Oid> {
+    let tag_names = match repo.tag_names(None) {
+        Ok(names) => names,
+        Err(_) => return None,
+    };
+
+    tag_names.iter()
+        .filter_map(|name| name)
+        .filter_map(|name| repo.find_reference(&format!("refs/tags/{}", name)).ok())
+        .filter_map(|reference| reference.peel_to_commit().ok())
+        .max_by_key(|commit| commit.time().seconds())
+        .map(|commit| commit.id())
+}
+
+/// Walks every tag in the repository and returns a map from the OID of the
+/// commit it points at to the tag's name, so a commit outside the
+/// unreleased range can still be attributed to the release it shipped in
+/// (mirrors gib's `retrieve_commit_tag_map`).
+pub fn retrieve_commit_tag_map(repo: &Repository) -> Result<HashMap<Oid, String>, git2::Error> {
+    let tag_names = try!(repo.tag_names(None));
+
+    let mut map = HashMap::new();
+    for name in tag_names.iter().filter_map(|name| name) {
+        let reference = match repo.find_reference(&format!("refs/tags/{}", name)) {
+            Ok(reference) => reference,
+            Err(_) => continue,
+        };
+
+        if let Ok(commit) = reference.peel_to_commit() {
+            map.insert(commit.id(), name.to_owned());
+        }
+    }
+
+    Ok(map)
+}
+
+/// Stages the release artifacts (manifest, lockfile, changelog) and commits
+/// them using `config.commit_message_template`.
+pub fn commit_files(config: &Config) -> Result<Oid, git2::Error> {
+    let repo = &config.repository;
+
+    let mut index = try!(repo.index());
+    try!(index.add_path(Path::new("Cargo.toml")));
+    try!(index.add_path(Path::new("Cargo.lock")));
+    try!(index.add_path(Path::new(&config.changelog_filename)));
+    try!(index.write());
+
+    let tree_id = try!(index.write_tree());
+    let tree = try!(repo.find_tree(tree_id));
+    let parent = try!(try!(repo.head()).peel_to_commit());
+
+    let message = config.commit_message_template.replace("{}", &config.new_version_string());
+
+    repo.commit(Some("HEAD"), &config.signature, &config.signature, &message, &tree, &[&parent])
+}
+
+/// Creates an annotated tag named `name` pointing at `HEAD`.
+pub fn tag(repository_path: &str, name: &str, message: &str) -> Result<(), git2::Error> {
+    let repo = try!(Repository::open(repository_path));
+    let commit = try!(try!(repo.head()).peel_to_commit());
+    let signature = try!(get_signature(&repo));
+
+    try!(repo.tag(name, commit.as_object(), &signature, message, false));
+    Ok(())
+}
@@ -9,6 +9,7 @@ mod changelog;
 mod commit_analyzer;
 mod cargo;
 mod error;
+mod config;
 
 extern crate rustc_serialize;
 extern crate toml;
@@ -16,16 +17,24 @@ extern crate regex;
 extern crate semver;
 extern crate docopt;
 extern crate git2;
-extern crate clog;
+extern crate tera;
+extern crate serde_json;
+extern crate time;
 
 use docopt::Docopt;
 use commit_analyzer::CommitType;
+use std::collections::HashMap;
 use std::process;
 use semver::Version;
 use std::env;
 use std::path::PathBuf;
-use std::error::Error;
 use git2::{Repository, Signature};
+use config::FileConfig;
+use error::Error;
+
+const DEFAULT_CHANGELOG_FILENAME: &'static str = "CHANGELOG.md";
+const DEFAULT_TAG_NAME_TEMPLATE: &'static str = "v{}";
+const DEFAULT_COMMIT_MESSAGE_TEMPLATE: &'static str = "chore(release): {}";
 
 const VERSION: &'static str = env!("CARGO_PKG_VERSION");
 const USAGE: &'static str = "
@@ -40,6 +49,8 @@ Options:
   --version              Show version.
   -p PATH, --path=PATH   Specifies the repository path. [default: .]
   -w, --write            Run with writing the changes afterwards.
+  --scope=SCOPE          Limit the changelog to commits with this scope.
+  --publish              Publish the crate to crates.io after tagging.
 ";
 
 #[derive(Debug, RustcDecodable)]
@@ -47,6 +58,8 @@ struct Args {
     flag_path: String,
     flag_write: bool,
     flag_version: bool,
+    flag_scope: Option<String>,
+    flag_publish: bool,
 }
 
 pub struct Config {
@@ -56,6 +69,13 @@ pub struct Config {
     current_version: Version,
     new_version: Version,
     signature: Signature<'static>,
+    changelog_filename: String,
+    tag_name_template: String,
+    commit_message_template: String,
+    commit_types: HashMap<String, String>,
+    branch_whitelist: Option<Vec<String>>,
+    scope_filter: Option<String>,
+    publish: bool,
 }
 
 impl Config {
@@ -84,6 +104,10 @@ fn ci_env_set() -> bool {
     env::var("CI").is_ok()
 }
 
+fn current_branch_name(repository: &Repository) -> Option<String> {
+    repository.head().ok().and_then(|head| head.shorthand().map(String::from))
+}
+
 fn main() {
     let args: Args = Docopt::new(USAGE)
         .and_then(|d| d.decode())
@@ -94,6 +118,15 @@ fn main() {
         process::exit(0);
     }
 
+    println!("semantic.rs 🚀");
+
+    if let Err(err) = run(&args) {
+        logger::stderr(err.to_string());
+        process::exit(err.exit_code());
+    }
+}
+
+fn run(args: &Args) -> Result<(), Error> {
     let is_dry_run = if ci_env_set() {
         false
     }
@@ -101,51 +134,17 @@ fn main() {
         !args.flag_write
     };
 
-    println!("semantic.rs 🚀");
-
     logger::stdout("Analyzing your repository");
     let repository_path = &args.flag_path;
 
-    let repo = match git2::Repository::open(repository_path) {
-        Ok(repo) => repo,
-        Err(e) => {
-            logger::stderr(format!("Could not open the git repository: {:?}", e));
-            process::exit(1);
-        }
-    };
-
-    let signature = match git::get_signature(&repo) {
-        Ok(sig) => sig,
-        Err(e) => {
-            logger::stderr(format!("Failed to get the committer's name and email address: {}", e.description()));
-            logger::stderr(r"
-A release commit needs a committer name and email address.
-We tried fetching it from different locations, but couldn't find one.
-
-Committer information is taken from the following environment variables, if set:
-
-GIT_COMMITTER_NAME
-GIT_COMMITTER_EMAIL
-
-If none is set the normal git config is tried in the following order:
-
-Local repository config
-User config
-Global config");
-            process::exit(1);
-        }
-    };
-
-    let version = match toml_file::read_from_file(repository_path) {
-        Ok(toml) => toml,
-        Err(e) => {
-            logger::stderr(format!("Reading `Cargo.toml` failed: {:?}", e));
-            process::exit(1);
-        }
-    };
+    let repo = try!(git2::Repository::open(repository_path).map_err(Error::OpenRepository));
+    let signature = try!(git::get_signature(&repo).map_err(Error::Signature));
 
+    let version = try!(toml_file::read_from_file(repository_path).map_err(Error::ReadManifest));
     let version = Version::parse(&version).expect("Not a valid version");
 
+    let file_config = try!(FileConfig::from_repository_path(repository_path));
+
     let mut config = Config {
         repository_path: PathBuf::from(repository_path),
         repository: repo,
@@ -153,13 +152,34 @@ Global config");
         current_version: version,
         new_version: Version::parse("0.0.0").unwrap(),
         signature: signature,
+        changelog_filename: file_config.changelog_filename.unwrap_or_else(|| DEFAULT_CHANGELOG_FILENAME.to_owned()),
+        tag_name_template: file_config.tag_name_template.unwrap_or_else(|| DEFAULT_TAG_NAME_TEMPLATE.to_owned()),
+        commit_message_template: file_config.commit_message_template.unwrap_or_else(|| DEFAULT_COMMIT_MESSAGE_TEMPLATE.to_owned()),
+        commit_types: {
+            let mut commit_types = commit_analyzer::default_commit_types();
+            commit_types.extend(file_config.commit_types.unwrap_or_else(HashMap::new));
+            commit_types
+        },
+        branch_whitelist: file_config.branch_whitelist,
+        scope_filter: args.flag_scope.clone(),
+        publish: args.flag_publish || file_config.publish.unwrap_or(false),
     };
 
+    if let Some(branch) = current_branch_name(&config.repository) {
+        let is_whitelisted = config.branch_whitelist.as_ref()
+            .map_or(true, |whitelist| whitelist.iter().any(|b| b == &branch));
+
+        if !is_whitelisted {
+            logger::stdout(format!("Current branch `{}` is not in the configured release branch whitelist. Skipping release.", branch));
+            return Ok(());
+        }
+    }
+
     logger::stdout(format!("Current version: {}", config.current_version_string()));
 
     logger::stdout("Analyzing commits");
 
-    let bump = git::version_bump_since_latest(&config);
+    let bump = try!(git::version_bump_since_latest(&config).map_err(Error::CommitAnalysis));
     if is_dry_run {
         logger::stdout(format!("Commits analyzed. Bump would be {:?}", bump));
     }
@@ -170,7 +190,7 @@ Global config");
         Some(new_version) => new_version,
         None => {
             logger::stdout("No version bump. Nothing to do.");
-            process::exit(0);
+            return Ok(());
         }
     };
     config.new_version = new_version;
@@ -179,74 +199,48 @@ Global config");
     if is_dry_run {
         logger::stdout(format!("New version would be: {}", new_version));
         logger::stdout("Would write the following Changelog:");
-        let changelog = match changelog::generate(&config) {
-            Ok(log) => log,
-            Err(err) => {
-                logger::stderr(format!("Generating Changelog failed: {:?}", err));
-                process::exit(1);
-            }
-        };
+        let changelog = try!(changelog::generate(&config));
         logger::stdout("====================================");
         logger::stdout(changelog);
         logger::stdout("====================================");
         logger::stdout("Would create annotated git tag");
+        if config.publish {
+            logger::stdout("Would publish to crates.io");
+        }
     }
     else {
         logger::stdout(format!("New version: {}", new_version));
 
-        match toml_file::write_new_version(repository_path, &new_version) {
-            Ok(_)    => { },
-            Err(err) => {
-                logger::stderr(format!("Writing `Cargo.toml` failed: {:?}", err));
-                process::exit(1);
-            }
-        }
+        try!(toml_file::write_new_version(repository_path, &new_version).map_err(Error::WriteManifest));
 
-        logger::stdout(format!("Writing Changelog"));
-        match changelog::write(&config) {
-            Ok(_)    => { },
-            Err(err) => {
-                logger::stderr(format!("Writing Changelog failed: {:?}", err));
-                process::exit(1);
-            }
-        }
+        logger::stdout("Writing Changelog");
+        try!(changelog::write(&config));
 
         logger::stdout("Updating lockfile");
         if !cargo::update_lockfile(&config) {
-            logger::stderr("`cargo fetch` failed. See above for the cargo error message.");
-            process::exit(1);
+            return Err(Error::Lockfile);
         }
 
         logger::stdout("Package crate");
         if !cargo::package(&config) {
-            logger::stderr("`cargo package` failed. See above for the cargo error message.");
-            process::exit(1);
+            return Err(Error::Package);
         }
 
-        match git::commit_files(&config) {
-            Ok(_)    => { },
-            Err(err) => {
-                logger::stderr(format!("Committing files failed: {:?}", err));
-                process::exit(1);
-            }
-        }
+        try!(git::commit_files(&config).map_err(Error::Commit));
 
         logger::stdout("Creating annotated git tag");
-        let tag_message = match changelog::generate(&config) {
-            Ok(msg) => msg,
-            Err(err) => {
-                logger::stderr(format!("Can't generate changelog: {:?}", err));
-                process::exit(1);
-            }
-        };
-
-        let tag_name = format!("v{}", new_version);
-        match git::tag(repository_path, &tag_name, &tag_message) {
-            Ok(_) => { },
-            Err(err) => {
-                logger::stderr(format!("Failed to create git tag: {:?}", err));
-                process::exit(1);
+        let tag_message = try!(changelog::generate(&config));
+
+        let tag_name = config.tag_name_template.replace("{}", &new_version);
+        try!(git::tag(repository_path, &tag_name, &tag_message).map_err(Error::Tag));
+
+        if config.publish {
+            logger::stdout("Publishing to crates.io");
+            if !cargo::publish(&config) {
+                return Err(Error::Publish);
             }
         }
     }
+
+    Ok(())
 }